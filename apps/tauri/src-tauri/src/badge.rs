@@ -0,0 +1,176 @@
+use log::info;
+use tauri::AppHandle;
+#[cfg(target_os = "windows")]
+use tauri::Manager;
+
+/// Render the unread-mail count on the platform's dock/taskbar badge for the `main` window.
+pub(crate) fn set_badge_count(_app: &AppHandle, count: i32) -> Result<(), String> {
+    #[cfg(target_os = "macos")]
+    return set_macos_dock_badge(count);
+
+    #[cfg(target_os = "windows")]
+    {
+        let window = _app
+            .get_webview_window("main")
+            .ok_or_else(|| "main window not found".to_string())?;
+        return set_windows_taskbar_overlay(&window, count);
+    }
+
+    #[cfg(target_os = "linux")]
+    return set_linux_launcher_entry(count);
+
+    #[cfg(not(any(target_os = "macos", target_os = "windows", target_os = "linux")))]
+    {
+        let _ = count;
+        Ok(())
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn set_macos_dock_badge(count: i32) -> Result<(), String> {
+    use cocoa::appkit::NSApp;
+    use cocoa::base::nil;
+    use cocoa::foundation::NSString;
+    use objc::{msg_send, sel, sel_impl};
+
+    unsafe {
+        // Empty string clears the badge; non-zero counts render as their decimal label.
+        let label = if count == 0 {
+            NSString::alloc(nil).init_str("")
+        } else {
+            NSString::alloc(nil).init_str(&count.to_string())
+        };
+        let dock_tile: cocoa::base::id = msg_send![NSApp(), dockTile];
+        let _: () = msg_send![dock_tile, setBadgeLabel: label];
+    }
+
+    info!("Set macOS dock badge to {}", count);
+    Ok(())
+}
+
+#[cfg(target_os = "windows")]
+fn set_windows_taskbar_overlay(window: &tauri::WebviewWindow, count: i32) -> Result<(), String> {
+    use windows::core::Interface;
+    use windows::Win32::Foundation::HWND;
+    use windows::Win32::System::Com::{CoCreateInstance, CLSCTX_ALL};
+    use windows::Win32::UI::Shell::{ITaskbarList3, TaskbarList};
+
+    let hwnd = HWND(window.hwnd().map_err(|e| e.to_string())?.0);
+
+    unsafe {
+        let taskbar: ITaskbarList3 =
+            CoCreateInstance(&TaskbarList, None, CLSCTX_ALL).map_err(|e| e.to_string())?;
+
+        if count == 0 {
+            taskbar.SetOverlayIcon(hwnd, None, None).map_err(|e| e.to_string())?;
+        } else {
+            let icon = render_count_icon(count)?;
+            let description = format!("{} unread", count);
+            taskbar
+                .SetOverlayIcon(hwnd, icon, &description)
+                .map_err(|e| e.to_string())?;
+        }
+    }
+
+    info!("Set Windows taskbar overlay icon to {}", count);
+    Ok(())
+}
+
+/// Rasterize the unread count into a small HICON suitable for `SetOverlayIcon`: a solid red
+/// disc (BGRA) with one short white bar per character of the count is enough to read at 16x16.
+#[cfg(target_os = "windows")]
+fn render_count_icon(count: i32) -> Result<windows::Win32::UI::WindowsAndMessaging::HICON, String> {
+    use windows::Win32::Graphics::Gdi::{CreateBitmap, DeleteObject};
+    use windows::Win32::UI::WindowsAndMessaging::{CreateIconIndirect, ICONINFO};
+
+    const SIZE: i32 = 16;
+    let digits = if count > 99 { "99+".to_string() } else { count.to_string() };
+    let pixels = render_badge_pixels(&digits, SIZE as u32);
+
+    unsafe {
+        let color =
+            CreateBitmap(SIZE, SIZE, 1, 32, Some(pixels.as_ptr() as *const _)).map_err(|e| e.to_string())?;
+        let mask = CreateBitmap(SIZE, SIZE, 1, 1, None).map_err(|e| e.to_string())?;
+
+        let icon_info = ICONINFO {
+            fIcon: true.into(),
+            xHotspot: 0,
+            yHotspot: 0,
+            hbmMask: mask,
+            hbmColor: color,
+        };
+        let icon = CreateIconIndirect(&icon_info).map_err(|e| e.to_string())?;
+
+        let _ = DeleteObject(color);
+        let _ = DeleteObject(mask);
+
+        Ok(icon)
+    }
+}
+
+/// Draw a filled red circle with one white bar per character, left-aligned from x=2, BGRA row-major.
+#[cfg(target_os = "windows")]
+fn render_badge_pixels(digits: &str, size: u32) -> Vec<u8> {
+    let mut pixels = vec![0u8; (size * size * 4) as usize];
+    let center = size as f32 / 2.0;
+    let radius = center - 1.0;
+
+    for y in 0..size {
+        for x in 0..size {
+            let dx = x as f32 - center;
+            let dy = y as f32 - center;
+            let idx = ((y * size + x) * 4) as usize;
+            if dx * dx + dy * dy <= radius * radius {
+                // Opaque red disc: B, G, R, A
+                pixels[idx] = 0x30;
+                pixels[idx + 1] = 0x30;
+                pixels[idx + 2] = 0xe0;
+                pixels[idx + 3] = 0xff;
+            }
+        }
+    }
+
+    // Coarse digit glyph: a short horizontal white bar per character, just enough to
+    // distinguish "1" from "99+" at 16x16 — full glyph rendering isn't worth it at this size.
+    let bar_width = (size / (digits.len() as u32 + 1)).max(2);
+    for (i, _) in digits.chars().enumerate() {
+        let x0 = 2 + i as u32 * (bar_width + 1);
+        for x in x0..(x0 + bar_width).min(size) {
+            for y in (size / 2 - 1)..(size / 2 + 1) {
+                let idx = ((y * size + x) * 4) as usize;
+                pixels[idx] = 0xff;
+                pixels[idx + 1] = 0xff;
+                pixels[idx + 2] = 0xff;
+                pixels[idx + 3] = 0xff;
+            }
+        }
+    }
+
+    pixels
+}
+
+#[cfg(target_os = "linux")]
+fn set_linux_launcher_entry(count: i32) -> Result<(), String> {
+    use std::collections::HashMap;
+    use zbus::blocking::Connection;
+    use zbus::zvariant::Value;
+
+    let connection = Connection::session().map_err(|e| e.to_string())?;
+
+    let mut properties: HashMap<&str, Value> = HashMap::new();
+    properties.insert("count", Value::from(count as i64));
+    properties.insert("count-visible", Value::from(count > 0));
+
+    connection
+        .emit_signal(
+            None::<&str>,
+            "/com/canonical/unity/launcherentry",
+            "com.canonical.Unity.LauncherEntry",
+            "Update",
+            &("application://nubo.desktop", properties),
+        )
+        .map_err(|e| e.to_string())?;
+
+    info!("Emitted Unity LauncherEntry update for count {}", count);
+    Ok(())
+}
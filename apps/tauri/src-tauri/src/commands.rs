@@ -1,18 +1,8 @@
-use log::{info, warn};
-use serde::{Deserialize, Serialize};
+use log::info;
 use tauri::Manager;
 use tauri_plugin_notification::NotificationExt;
-use tauri_plugin_store::StoreExt;
 
-/// Window state structure
-#[derive(Debug, Clone, Serialize, Deserialize, Default)]
-pub struct WindowState {
-    pub width: f64,
-    pub height: f64,
-    pub x: i32,
-    pub y: i32,
-    pub maximized: bool,
-}
+use crate::WindowState;
 
 /// Show a native notification
 #[tauri::command]
@@ -31,31 +21,42 @@ pub async fn show_notification(
     Ok(())
 }
 
-/// Set badge count (macOS only - placeholder)
+/// Set the unread-mail dock/taskbar badge count
 #[tauri::command]
-pub async fn set_badge_count(_app: tauri::AppHandle, count: i32) -> Result<(), String> {
+pub async fn set_badge_count(app: tauri::AppHandle, count: i32) -> Result<(), String> {
     info!("Setting badge count to: {}", count);
-    Ok(())
+    crate::badge::set_badge_count(&app, count)
 }
 
 /// Check for app updates (desktop only)
 #[cfg(desktop)]
 #[tauri::command]
 pub async fn check_for_updates(app: tauri::AppHandle) -> Result<String, String> {
-    use tauri_plugin_updater::UpdaterExt;
-
     info!("Manual update check requested");
 
-    let updater = app.updater().map_err(|e| e.to_string())?;
+    let settings = crate::load_settings(&app);
+    let updater =
+        crate::updater::updater_for_channel(&app, &settings.update_channel).map_err(|e| e.to_string())?;
 
     match updater.check().await {
         Ok(Some(update)) => {
             let version = update.version.clone();
             info!("Update found: {}", version);
+            crate::updater::emit_update_available(&app, &update);
+
+            if !crate::updater::should_install(
+                env!("CARGO_PKG_VERSION"),
+                &version,
+                &settings.update_channel,
+            ) {
+                return Ok(format!(
+                    "Update {} is not allowed on the {} channel",
+                    version, settings.update_channel
+                ));
+            }
+
             tauri::async_runtime::spawn(async move {
-                if let Err(e) = update.download_and_install(|_, _| {}, || {}).await {
-                    warn!("Failed to install update: {}", e);
-                }
+                crate::updater::install_update(&app, update).await;
             });
             Ok(format!("Update {} available - downloading...", version))
         }
@@ -63,7 +64,10 @@ pub async fn check_for_updates(app: tauri::AppHandle) -> Result<String, String>
             info!("No updates available");
             Ok("No updates available".to_string())
         }
-        Err(e) => Err(e.to_string()),
+        Err(e) => {
+            crate::updater::emit_update_error(&app, &e.to_string());
+            Err(e.to_string())
+        }
     }
 }
 
@@ -73,38 +77,46 @@ pub async fn check_for_updates(_app: tauri::AppHandle) -> Result<String, String>
     Ok("Updates not available on this platform".to_string())
 }
 
-/// Get OS theme (light/dark)
+/// Get the `main` window's actual current theme (light/dark)
 #[tauri::command]
-pub async fn get_os_theme() -> Result<String, String> {
-    Ok("system".to_string())
+pub async fn get_os_theme(app: tauri::AppHandle) -> Result<String, String> {
+    crate::theme::current_theme(&app)
 }
 
-/// Get saved window state
+/// Set the user's theme preference ("system"/"light"/"dark") and apply it immediately
 #[tauri::command]
-pub async fn get_window_state(app: tauri::AppHandle) -> Result<WindowState, String> {
-    let store = app.store(".nubo-settings.json").map_err(|e| e.to_string())?;
-
-    let state = store
-        .get("window_state")
-        .and_then(|v| serde_json::from_value(v.clone()).ok())
-        .unwrap_or_default();
+pub async fn set_theme_preference(app: tauri::AppHandle, preference: String) -> Result<(), String> {
+    crate::theme::set_theme_preference(&app, preference)
+}
 
-    Ok(state)
+/// Get the calling window's saved geometry
+#[tauri::command]
+pub async fn get_window_state(
+    app: tauri::AppHandle,
+    window: tauri::WebviewWindow,
+) -> Result<WindowState, String> {
+    let settings = crate::load_settings(&app);
+    Ok(settings
+        .window_states
+        .get(window.label())
+        .cloned()
+        .unwrap_or_default())
 }
 
-/// Save window state
+/// Save the calling window's current geometry
 #[tauri::command]
-pub async fn save_window_state_cmd(app: tauri::AppHandle) -> Result<(), String> {
-    if let Some(window) = app.get_webview_window("main") {
-        crate::save_window_state(&app, &window);
-    }
+pub async fn save_window_state_cmd(
+    app: tauri::AppHandle,
+    window: tauri::WebviewWindow,
+) -> Result<(), String> {
+    crate::save_window_state(&app, &window);
     Ok(())
 }
 
-/// Check if online
+/// Check if online, per the background connectivity monitor's cached state
 #[tauri::command]
-pub async fn is_online() -> Result<bool, String> {
-    Ok(true)
+pub async fn is_online(app: tauri::AppHandle) -> Result<bool, String> {
+    Ok(app.state::<crate::connectivity::ConnectivityMonitor>().is_online())
 }
 
 /// Get app version
@@ -113,26 +125,140 @@ pub async fn get_app_version() -> Result<String, String> {
     Ok(env!("CARGO_PKG_VERSION").to_string())
 }
 
-/// Rollback to previous version
+/// Roll back to the given retained version, or the most recently retained one if `version` is
+/// omitted, for the "pick a version" rollback menu.
+#[cfg(desktop)]
 #[tauri::command]
-pub async fn rollback_update(app: tauri::AppHandle) -> Result<String, String> {
-    let store = app.store(".nubo-settings.json").map_err(|e| e.to_string())?;
+pub async fn rollback_update(app: tauri::AppHandle, version: Option<String>) -> Result<String, String> {
+    crate::rollback::rollback_update(&app, version).await
+}
 
-    let last_version = store
-        .get("last_version")
-        .and_then(|v| v.as_str().map(String::from));
+#[cfg(not(desktop))]
+#[tauri::command]
+pub async fn rollback_update(_app: tauri::AppHandle, _version: Option<String>) -> Result<String, String> {
+    Ok("Rollback not available on this platform".to_string())
+}
 
-    match last_version {
-        Some(version) => {
-            info!("Rollback requested to version: {}", version);
-            Ok(format!(
-                "Previous version was {}. Please download from GitHub releases.",
-                version
-            ))
-        }
-        None => {
-            warn!("No previous version found for rollback");
-            Ok("No previous version available for rollback".to_string())
+/// List the retained versions available for rollback
+#[cfg(desktop)]
+#[tauri::command]
+pub async fn get_update_history(
+    app: tauri::AppHandle,
+) -> Result<Vec<crate::rollback::RetainedVersion>, String> {
+    Ok(crate::rollback::retained_versions(&app))
+}
+
+#[cfg(not(desktop))]
+#[tauri::command]
+pub async fn get_update_history(_app: tauri::AppHandle) -> Result<Vec<serde_json::Value>, String> {
+    Ok(Vec::new())
+}
+
+/// Toggle whether the app checks for updates on startup
+#[tauri::command]
+pub async fn set_update_check_enabled(app: tauri::AppHandle, enabled: bool) -> Result<(), String> {
+    info!("Setting update_check_enabled to: {}", enabled);
+    let mut settings = crate::load_settings(&app);
+    settings.update_check_enabled = enabled;
+    crate::save_settings(&app, &settings);
+    Ok(())
+}
+
+/// Reject anything other than the three known update channels, since `should_install` treats
+/// any non-"stable" channel as pre-release-eligible.
+fn validate_channel(channel: &str) -> Result<(), String> {
+    match channel {
+        "stable" | "beta" | "nightly" => Ok(()),
+        other => Err(format!("Invalid update channel: {}", other)),
+    }
+}
+
+/// Switch the update channel (stable/beta/nightly) and immediately re-check for updates
+#[cfg(desktop)]
+#[tauri::command]
+pub async fn set_update_channel(app: tauri::AppHandle, channel: String) -> Result<(), String> {
+    validate_channel(&channel)?;
+    info!("Setting update channel to: {}", channel);
+    let mut settings = crate::load_settings(&app);
+    settings.update_channel = channel;
+    crate::save_settings(&app, &settings);
+
+    tauri::async_runtime::spawn(async move {
+        crate::updater::check_and_install_update(&app).await;
+    });
+    Ok(())
+}
+
+#[cfg(not(desktop))]
+#[tauri::command]
+pub async fn set_update_channel(app: tauri::AppHandle, channel: String) -> Result<(), String> {
+    validate_channel(&channel)?;
+    let mut settings = crate::load_settings(&app);
+    settings.update_channel = channel;
+    crate::save_settings(&app, &settings);
+    Ok(())
+}
+
+/// Open the standalone compose window, optionally pre-filling a recipient. If one is already
+/// open, it's navigated to the new recipient (if any) and focused rather than left as-is.
+#[tauri::command]
+pub async fn open_compose_window(app: tauri::AppHandle, to: Option<String>) -> Result<(), String> {
+    if let Some(existing) = app.get_webview_window("compose") {
+        info!("Compose window already open, navigating and focusing it");
+        if let Some(to) = &to {
+            let nav_url = format!(
+                "https://nubo.email/mail/inbox?isComposeOpen=true&to={}",
+                urlencoding::encode(to)
+            );
+            let _ = existing.eval(&format!("window.location.href = '{}';", nav_url));
         }
+        let _ = existing.show();
+        let _ = existing.set_focus();
+        return Ok(());
+    }
+
+    let settings = crate::load_settings(&app);
+    let state = settings
+        .window_states
+        .get("compose")
+        .cloned()
+        .unwrap_or_else(WindowState::compose_default);
+
+    let mut compose_url = "https://nubo.email/mail/inbox?isComposeOpen=true".to_string();
+    if let Some(to) = &to {
+        compose_url.push_str(&format!("&to={}", urlencoding::encode(to)));
+    }
+
+    info!("Opening compose window for: {}", to.as_deref().unwrap_or(""));
+
+    let window = tauri::WebviewWindowBuilder::new(
+        &app,
+        "compose",
+        tauri::WebviewUrl::External(compose_url.parse().map_err(|e: url::ParseError| e.to_string())?),
+    )
+    .title("New message")
+    .inner_size(state.width, state.height)
+    .min_inner_size(480.0, 360.0)
+    .position(state.x as f64, state.y as f64)
+    .resizable(true)
+    .decorations(true)
+    .visible(true)
+    .visible_on_all_workspaces(state.visible_on_all_workspaces)
+    .build()
+    .map_err(|e| e.to_string())?;
+
+    if state.maximized {
+        let _ = window.maximize();
     }
+
+    let app_handle = app.clone();
+    let window_clone = window.clone();
+    window.on_window_event(move |event| {
+        if let tauri::WindowEvent::CloseRequested { .. } = event {
+            info!("Compose window closing, saving state...");
+            crate::save_window_state(&app_handle, &window_clone);
+        }
+    });
+
+    Ok(())
 }
@@ -0,0 +1,115 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use log::{info, warn};
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_notification::NotificationExt;
+
+use crate::load_settings;
+
+/// How long connectivity must stay down before we raise a native notification.
+const OFFLINE_NOTIFICATION_THRESHOLD: Duration = Duration::from_secs(60);
+const MAX_BACKOFF: Duration = Duration::from_secs(300);
+
+/// Cached reachability state, shared via `app.manage(...)` so `is_online` is a cheap read.
+pub(crate) struct ConnectivityMonitor {
+    online: AtomicBool,
+}
+
+impl ConnectivityMonitor {
+    pub(crate) fn new() -> Self {
+        Self {
+            online: AtomicBool::new(true),
+        }
+    }
+
+    pub(crate) fn is_online(&self) -> bool {
+        self.online.load(Ordering::Relaxed)
+    }
+}
+
+fn emit_to_main<P: Serialize + Clone>(app: &AppHandle, event: &str, payload: P) {
+    if let Err(e) = app.emit_to("main", event, payload) {
+        warn!("Failed to emit {} event: {}", event, e);
+    }
+}
+
+async fn probe(host: &str) -> bool {
+    let url = format!("https://{}/", host);
+    match reqwest::Client::new()
+        .head(&url)
+        .timeout(Duration::from_secs(5))
+        .send()
+        .await
+    {
+        Ok(response) => response.status().is_success() || response.status().is_redirection(),
+        Err(_) => false,
+    }
+}
+
+fn notify_offline(app: &AppHandle) {
+    if let Err(e) = app
+        .notification()
+        .builder()
+        .title("You're offline")
+        .body("Nubo will keep retrying in the background.")
+        .show()
+    {
+        warn!("Failed to show offline notification: {}", e);
+    }
+}
+
+async fn run_monitor(app: AppHandle) {
+    let mut offline_since: Option<Instant> = None;
+    let mut notified = false;
+    let mut backoff = Duration::from_secs(5);
+
+    loop {
+        let settings = load_settings(&app);
+        let interval = Duration::from_secs(settings.connectivity_probe_interval_secs);
+        let reachable = probe(&settings.connectivity_probe_host).await;
+
+        let monitor = app.state::<ConnectivityMonitor>();
+        let was_online = monitor.is_online();
+
+        if reachable != was_online {
+            monitor.online.store(reachable, Ordering::Relaxed);
+            if reachable {
+                info!("Connectivity restored");
+                emit_to_main(&app, "network://online", ());
+                offline_since = None;
+                notified = false;
+                backoff = interval;
+            } else {
+                warn!("Connectivity lost");
+                emit_to_main(&app, "network://offline", ());
+                offline_since = Some(Instant::now());
+            }
+        }
+
+        if reachable {
+            tokio::time::sleep(interval).await;
+        } else {
+            if !notified && settings.notifications_enabled {
+                if let Some(since) = offline_since {
+                    if since.elapsed() >= OFFLINE_NOTIFICATION_THRESHOLD {
+                        notify_offline(&app);
+                        notified = true;
+                    }
+                }
+            }
+            // Exponential backoff while offline so we don't hammer the probe host.
+            backoff = (backoff * 2).min(MAX_BACKOFF);
+            tokio::time::sleep(backoff).await;
+        }
+    }
+}
+
+/// Spawn the background connectivity monitor. Call once from `setup` after `app.manage(...)`.
+pub(crate) fn spawn_monitor(app: &AppHandle) {
+    let app_handle = app.clone();
+    tauri::async_runtime::spawn(async move {
+        run_monitor(app_handle).await;
+    });
+}
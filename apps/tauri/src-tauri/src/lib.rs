@@ -1,11 +1,20 @@
+use std::collections::HashMap;
+
 use log::{error, info, warn};
 use serde::{Deserialize, Serialize};
 use tauri::{Manager, WebviewUrl, WebviewWindowBuilder};
 use tauri_plugin_store::StoreExt;
 
+mod badge;
 mod commands;
+mod connectivity;
+#[cfg(desktop)]
+mod rollback;
+mod theme;
+#[cfg(desktop)]
+mod updater;
 
-/// Window state for persistence
+/// Per-window state for persistence, keyed by window label in `AppSettings.window_states`
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct WindowState {
     pub width: f64,
@@ -13,36 +22,70 @@ pub struct WindowState {
     pub x: i32,
     pub y: i32,
     pub maximized: bool,
+    /// Added after the initial release; defaults to `false` so older stores without this
+    /// field still deserialize instead of losing the rest of their saved geometry.
+    #[serde(default)]
+    pub visible_on_all_workspaces: bool,
+}
+
+impl WindowState {
+    pub(crate) fn main_default() -> Self {
+        Self {
+            width: 1400.0,
+            height: 900.0,
+            x: 100,
+            y: 100,
+            maximized: false,
+            visible_on_all_workspaces: false,
+        }
+    }
+
+    /// Compose windows default to floating across workspaces, like a pinned notification.
+    pub(crate) fn compose_default() -> Self {
+        Self {
+            width: 640.0,
+            height: 520.0,
+            x: 160,
+            y: 160,
+            maximized: false,
+            visible_on_all_workspaces: true,
+        }
+    }
 }
 
 /// App settings stored persistently
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppSettings {
-    pub window_state: WindowState,
+    pub window_states: HashMap<String, WindowState>,
     pub last_version: Option<String>,
     pub update_check_enabled: bool,
     pub notifications_enabled: bool,
+    pub update_channel: String,
+    pub theme_preference: String,
+    pub connectivity_probe_host: String,
+    pub connectivity_probe_interval_secs: u64,
 }
 
 impl Default for AppSettings {
     fn default() -> Self {
+        let mut window_states = HashMap::new();
+        window_states.insert("main".to_string(), WindowState::main_default());
+
         Self {
-            window_state: WindowState {
-                width: 1400.0,
-                height: 900.0,
-                x: 100,
-                y: 100,
-                maximized: false,
-            },
+            window_states,
             last_version: None,
             update_check_enabled: true,
             notifications_enabled: true,
+            update_channel: "stable".to_string(),
+            theme_preference: "system".to_string(),
+            connectivity_probe_host: "nubo.email".to_string(),
+            connectivity_probe_interval_secs: 30,
         }
     }
 }
 
 /// Load settings from store
-fn load_settings(app: &tauri::AppHandle) -> AppSettings {
+pub(crate) fn load_settings(app: &tauri::AppHandle) -> AppSettings {
     let store = match app.store(".nubo-settings.json") {
         Ok(s) => s,
         Err(e) => {
@@ -51,10 +94,19 @@ fn load_settings(app: &tauri::AppHandle) -> AppSettings {
         }
     };
 
-    let window_state = store
-        .get("window_state")
+    let window_states: HashMap<String, WindowState> = store
+        .get("window_states")
         .and_then(|v| serde_json::from_value(v.clone()).ok())
-        .unwrap_or_default();
+        .unwrap_or_else(|| {
+            // Migration: older stores kept a single `window_state` for the main window only
+            let mut map = HashMap::new();
+            let main_state = store
+                .get("window_state")
+                .and_then(|v| serde_json::from_value(v.clone()).ok())
+                .unwrap_or_else(WindowState::main_default);
+            map.insert("main".to_string(), main_state);
+            map
+        });
 
     let last_version = store
         .get("last_version")
@@ -70,16 +122,43 @@ fn load_settings(app: &tauri::AppHandle) -> AppSettings {
         .and_then(|v| v.as_bool())
         .unwrap_or(true);
 
+    // Migration: older stores predate `update_channel`, default to "stable"
+    let update_channel = store
+        .get("update_channel")
+        .and_then(|v| v.as_str().map(String::from))
+        .unwrap_or_else(|| "stable".to_string());
+
+    // Migration: older stores predate `theme_preference`, default to "system"
+    let theme_preference = store
+        .get("theme_preference")
+        .and_then(|v| v.as_str().map(String::from))
+        .unwrap_or_else(|| "system".to_string());
+
+    // Migration: older stores predate the connectivity probe settings
+    let connectivity_probe_host = store
+        .get("connectivity_probe_host")
+        .and_then(|v| v.as_str().map(String::from))
+        .unwrap_or_else(|| "nubo.email".to_string());
+
+    let connectivity_probe_interval_secs = store
+        .get("connectivity_probe_interval_secs")
+        .and_then(|v| v.as_u64())
+        .unwrap_or(30);
+
     AppSettings {
-        window_state,
+        window_states,
         last_version,
         update_check_enabled,
         notifications_enabled,
+        update_channel,
+        theme_preference,
+        connectivity_probe_host,
+        connectivity_probe_interval_secs,
     }
 }
 
 /// Save settings to store
-fn save_settings(app: &tauri::AppHandle, settings: &AppSettings) {
+pub(crate) fn save_settings(app: &tauri::AppHandle, settings: &AppSettings) {
     let store = match app.store(".nubo-settings.json") {
         Ok(s) => s,
         Err(e) => {
@@ -88,35 +167,55 @@ fn save_settings(app: &tauri::AppHandle, settings: &AppSettings) {
         }
     };
 
-    if let Ok(value) = serde_json::to_value(&settings.window_state) {
-        let _ = store.set("window_state", value);
+    if let Ok(value) = serde_json::to_value(&settings.window_states) {
+        let _ = store.set("window_states", value);
     }
     if let Some(ref version) = settings.last_version {
         let _ = store.set("last_version", serde_json::json!(version));
     }
     let _ = store.set("update_check_enabled", serde_json::json!(settings.update_check_enabled));
     let _ = store.set("notifications_enabled", serde_json::json!(settings.notifications_enabled));
+    let _ = store.set("update_channel", serde_json::json!(settings.update_channel));
+    let _ = store.set("theme_preference", serde_json::json!(settings.theme_preference));
+    let _ = store.set(
+        "connectivity_probe_host",
+        serde_json::json!(settings.connectivity_probe_host),
+    );
+    let _ = store.set(
+        "connectivity_probe_interval_secs",
+        serde_json::json!(settings.connectivity_probe_interval_secs),
+    );
     let _ = store.save();
 }
 
-/// Save current window state
+/// Save the given window's current geometry under its own label, leaving other windows' saved
+/// state untouched.
 pub fn save_window_state(app: &tauri::AppHandle, window: &tauri::WebviewWindow) {
     let mut settings = load_settings(app);
+    let mut state = settings
+        .window_states
+        .get(window.label())
+        .cloned()
+        .unwrap_or_else(|| match window.label() {
+            "compose" => WindowState::compose_default(),
+            _ => WindowState::default(),
+        });
 
     if let Ok(size) = window.inner_size() {
-        settings.window_state.width = size.width as f64;
-        settings.window_state.height = size.height as f64;
+        state.width = size.width as f64;
+        state.height = size.height as f64;
     }
 
     if let Ok(position) = window.outer_position() {
-        settings.window_state.x = position.x;
-        settings.window_state.y = position.y;
+        state.x = position.x;
+        state.y = position.y;
     }
 
     if let Ok(maximized) = window.is_maximized() {
-        settings.window_state.maximized = maximized;
+        state.maximized = maximized;
     }
 
+    settings.window_states.insert(window.label().to_string(), state);
     save_settings(app, &settings);
 }
 
@@ -166,24 +265,38 @@ pub fn run() {
 
             // Load saved settings
             let settings = load_settings(app.handle());
-            info!("Loaded settings: window {}x{}", settings.window_state.width, settings.window_state.height);
+            let main_state = settings
+                .window_states
+                .get("main")
+                .cloned()
+                .unwrap_or_else(WindowState::main_default);
+            info!("Loaded settings: window {}x{}", main_state.width, main_state.height);
+
+            // Connectivity monitor: cached state for `is_online`, background probe loop
+            app.manage(connectivity::ConnectivityMonitor::new());
+            connectivity::spawn_monitor(app.handle());
 
             // Create main window
             let url = WebviewUrl::External("https://nubo.email".parse().unwrap());
 
+            let theme_override = theme::parse_preference(&settings.theme_preference)
+                .unwrap_or(None);
+
             let window = WebviewWindowBuilder::new(app, "main", url)
                 .title("Nubo")
-                .inner_size(settings.window_state.width, settings.window_state.height)
+                .inner_size(main_state.width, main_state.height)
                 .min_inner_size(800.0, 600.0)
-                .position(settings.window_state.x as f64, settings.window_state.y as f64)
+                .position(main_state.x as f64, main_state.y as f64)
                 .resizable(true)
                 .fullscreen(false)
                 .decorations(true)
                 .visible(true)
+                .theme(theme_override)
+                .visible_on_all_workspaces(main_state.visible_on_all_workspaces)
                 .build()?;
 
             // Restore maximized state
-            if settings.window_state.maximized {
+            if main_state.maximized {
                 let _ = window.maximize();
             }
 
@@ -194,14 +307,18 @@ pub fn run() {
                 let _ = window.set_title_bar_style(TitleBarStyle::Overlay);
             }
 
-            // Save window state on close
+            // Save window state on close, and relay OS theme changes to the web frontend
             let app_handle = app.handle().clone();
             let window_clone = window.clone();
-            window.on_window_event(move |event| {
-                if let tauri::WindowEvent::CloseRequested { .. } = event {
+            window.on_window_event(move |event| match event {
+                tauri::WindowEvent::CloseRequested { .. } => {
                     info!("Window closing, saving state...");
                     save_window_state(&app_handle, &window_clone);
                 }
+                tauri::WindowEvent::ThemeChanged(new_theme) => {
+                    theme::emit_theme_changed(&app_handle, *new_theme);
+                }
+                _ => {}
             });
 
             // Handle deep links
@@ -216,14 +333,19 @@ pub fn run() {
                 });
             }
 
-            // Check for updates on startup (silent)
+            // Check for updates on startup (silent), respecting the stored preference
             #[cfg(desktop)]
             {
                 let app_handle_updater = app.handle().clone();
+                let update_check_enabled = settings.update_check_enabled;
                 tauri::async_runtime::spawn(async move {
+                    if !update_check_enabled {
+                        info!("Update checks disabled by user preference, skipping startup check");
+                        return;
+                    }
                     // Wait before checking
                     std::thread::sleep(std::time::Duration::from_secs(5));
-                    check_and_install_update(&app_handle_updater).await;
+                    updater::check_and_install_update(&app_handle_updater).await;
                 });
             }
 
@@ -240,6 +362,11 @@ pub fn run() {
             commands::is_online,
             commands::get_app_version,
             commands::rollback_update,
+            commands::set_update_check_enabled,
+            commands::set_update_channel,
+            commands::get_update_history,
+            commands::set_theme_preference,
+            commands::open_compose_window,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -250,18 +377,15 @@ fn handle_deep_link(app: &tauri::AppHandle, url: &str) {
 
     if url.starts_with("mailto:") {
         let cleaned = url.replace("mailto:", "");
-        let email = cleaned.split('?').next().unwrap_or("");
+        let email = cleaned.split('?').next().unwrap_or("").to_string();
 
-        if let Some(window) = app.get_webview_window("main") {
-            let _ = window.show();
-            let _ = window.set_focus();
-            let nav_url = format!(
-                "https://nubo.email/mail/inbox?isComposeOpen=true&to={}",
-                urlencoding::encode(email)
-            );
-            info!("Navigating to compose for: {}", email);
-            let _ = window.eval(&format!("window.location.href = '{}';", nav_url));
-        }
+        info!("Opening compose window for: {}", email);
+        let app_handle = app.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = commands::open_compose_window(app_handle, Some(email)).await {
+                warn!("Failed to open compose window from deep link: {}", e);
+            }
+        });
     } else if url.starts_with("nubo://") {
         if let Some(window) = app.get_webview_window("main") {
             let _ = window.show();
@@ -273,56 +397,3 @@ fn handle_deep_link(app: &tauri::AppHandle, url: &str) {
         }
     }
 }
-
-/// Check for updates and install silently
-#[cfg(desktop)]
-async fn check_and_install_update(app: &tauri::AppHandle) {
-    use tauri_plugin_updater::UpdaterExt;
-
-    info!("Checking for updates...");
-
-    let updater = match app.updater() {
-        Ok(u) => u,
-        Err(e) => {
-            warn!("Failed to get updater: {}", e);
-            return;
-        }
-    };
-
-    match updater.check().await {
-        Ok(Some(update)) => {
-            info!("Update available: {} -> {}", env!("CARGO_PKG_VERSION"), update.version);
-
-            // Save current version for rollback
-            let mut settings = load_settings(app);
-            settings.last_version = Some(env!("CARGO_PKG_VERSION").to_string());
-            save_settings(app, &settings);
-
-            info!("Downloading update...");
-            match update.download_and_install(
-                |downloaded, total| {
-                    if let Some(t) = total {
-                        let percent = (downloaded as f64 / t as f64) * 100.0;
-                        info!("Download progress: {:.1}%", percent);
-                    }
-                },
-                || {
-                    info!("Download complete, preparing to install...");
-                },
-            ).await {
-                Ok(_) => {
-                    info!("Update installed successfully. Will apply on next restart.");
-                }
-                Err(e) => {
-                    error!("Failed to install update: {}", e);
-                }
-            }
-        }
-        Ok(None) => {
-            info!("No updates available");
-        }
-        Err(e) => {
-            warn!("Failed to check for updates: {}", e);
-        }
-    }
-}
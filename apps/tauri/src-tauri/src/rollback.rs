@@ -0,0 +1,176 @@
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use tauri::AppHandle;
+use tauri_plugin_shell::ShellExt;
+use tauri_plugin_store::StoreExt;
+
+use crate::updater::{emit_update_downloaded, emit_update_error, emit_update_installed, emit_update_progress};
+
+const MAX_RETAINED_VERSIONS: usize = 5;
+const UPDATE_HISTORY_KEY: &str = "update_history";
+
+/// A previously-installed build retained so `rollback_update` can fetch it back.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RetainedVersion {
+    pub version: String,
+    pub url: String,
+    pub signature: String,
+}
+
+fn target_triple() -> &'static str {
+    match (std::env::consts::OS, std::env::consts::ARCH) {
+        ("macos", "aarch64") => "darwin-aarch64",
+        ("macos", _) => "darwin-x86_64",
+        ("windows", "aarch64") => "windows-aarch64",
+        ("windows", _) => "windows-x86_64",
+        ("linux", "aarch64") => "linux-aarch64",
+        _ => "linux-x86_64",
+    }
+}
+
+/// The retained version ring, oldest first.
+pub(crate) fn retained_versions(app: &AppHandle) -> Vec<RetainedVersion> {
+    let store = match app.store(".nubo-settings.json") {
+        Ok(s) => s,
+        Err(e) => {
+            warn!("Failed to open settings store: {}", e);
+            return Vec::new();
+        }
+    };
+
+    store
+        .get(UPDATE_HISTORY_KEY)
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+        .unwrap_or_default()
+}
+
+fn save_retained_versions(app: &AppHandle, versions: &[RetainedVersion]) {
+    let store = match app.store(".nubo-settings.json") {
+        Ok(s) => s,
+        Err(e) => {
+            error!("Failed to open settings store for saving: {}", e);
+            return;
+        }
+    };
+    let _ = store.set(UPDATE_HISTORY_KEY, serde_json::json!(versions));
+    let _ = store.save();
+}
+
+/// Resolve the download URL and signature for the build currently running, and record it
+/// in a bounded ring so `rollback_update` can fetch it back after a later upgrade.
+pub(crate) async fn retain_current_build(app: &AppHandle) {
+    let current = env!("CARGO_PKG_VERSION");
+    let manifest_url = format!("https://nubo.email/releases/{}/{}", target_triple(), current);
+
+    let manifest: RetainedVersion = match reqwest::get(&manifest_url)
+        .await
+        .and_then(|r| r.error_for_status())
+    {
+        Ok(response) => match response.json().await {
+            Ok(m) => m,
+            Err(e) => {
+                warn!("Failed to parse release manifest for {}: {}", current, e);
+                return;
+            }
+        },
+        Err(e) => {
+            warn!("Failed to resolve release artifact for {}: {}", current, e);
+            return;
+        }
+    };
+
+    let mut history = retained_versions(app);
+    history.retain(|v| v.version != manifest.version);
+    history.push(manifest);
+    while history.len() > MAX_RETAINED_VERSIONS {
+        history.remove(0);
+    }
+    save_retained_versions(app, &history);
+}
+
+fn verify_signature(bytes: &[u8], signature_b64: &str) -> Result<(), String> {
+    use minisign_verify::{PublicKey, Signature};
+
+    let pubkey = PublicKey::from_base64(option_env!("NUBO_UPDATER_PUBKEY").unwrap_or(""))
+        .map_err(|e| e.to_string())?;
+    let signature = Signature::decode(signature_b64).map_err(|e| e.to_string())?;
+    pubkey.verify(bytes, &signature, false).map_err(|e| e.to_string())
+}
+
+/// Fetch the retained installer for `version` (or the most recently retained one if `None`),
+/// verify its signature, and launch it. The entry is only removed from the retained history
+/// once the installer has actually launched — a transient download/verification/disk failure
+/// must leave it available to retry, not silently evict it.
+pub(crate) async fn rollback_update(app: &AppHandle, version: Option<String>) -> Result<String, String> {
+    let mut history = retained_versions(app);
+
+    let (idx, target) = match &version {
+        Some(v) => match history.iter().position(|r| &r.version == v) {
+            Some(idx) => (idx, history[idx].clone()),
+            None => {
+                warn!("Requested rollback version {} not found in retained history", v);
+                return Err(format!("Version {} is not available for rollback", v));
+            }
+        },
+        None => match history.len().checked_sub(1) {
+            Some(idx) => (idx, history[idx].clone()),
+            None => {
+                warn!("No previous version found for rollback");
+                return Ok("No previous version available for rollback".to_string());
+            }
+        },
+    };
+
+    info!("Rollback requested to version: {}", target.version);
+
+    let mut response = reqwest::get(&target.url)
+        .await
+        .and_then(|r| r.error_for_status())
+        .map_err(|e| e.to_string())?;
+    let total = response.content_length();
+
+    let mut bytes = Vec::new();
+    loop {
+        match response.chunk().await {
+            Ok(Some(chunk)) => {
+                bytes.extend_from_slice(&chunk);
+                emit_update_progress(app, bytes.len(), total);
+            }
+            Ok(None) => break,
+            Err(e) => return Err(e.to_string()),
+        }
+    }
+
+    if let Err(e) = verify_signature(&bytes, &target.signature) {
+        let message = format!("Signature verification failed for {}: {}", target.version, e);
+        error!("{}", message);
+        emit_update_error(app, &message);
+        return Err(message);
+    }
+
+    emit_update_downloaded(app);
+
+    let installer_path = std::env::temp_dir().join(format!("nubo-{}-installer", target.version));
+    if let Err(e) = std::fs::write(&installer_path, &bytes) {
+        let message = format!("Failed to write installer to disk: {}", e);
+        error!("{}", message);
+        emit_update_error(app, &message);
+        return Err(message);
+    }
+
+    match app.shell().open(installer_path.to_string_lossy(), None) {
+        Ok(_) => {
+            info!("Launched rollback installer for version {}", target.version);
+            history.remove(idx);
+            save_retained_versions(app, &history);
+            emit_update_installed(app);
+            Ok(format!("Rolling back to version {}", target.version))
+        }
+        Err(e) => {
+            let message = format!("Failed to launch rollback installer: {}", e);
+            error!("{}", message);
+            emit_update_error(app, &message);
+            Err(message)
+        }
+    }
+}
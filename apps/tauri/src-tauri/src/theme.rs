@@ -0,0 +1,55 @@
+use log::{info, warn};
+use tauri::{AppHandle, Emitter, Manager, Theme};
+
+use crate::{load_settings, save_settings};
+
+fn theme_to_str(theme: Theme) -> &'static str {
+    match theme {
+        Theme::Dark => "dark",
+        _ => "light",
+    }
+}
+
+pub(crate) fn parse_preference(preference: &str) -> Result<Option<Theme>, String> {
+    match preference {
+        "system" => Ok(None),
+        "light" => Ok(Some(Theme::Light)),
+        "dark" => Ok(Some(Theme::Dark)),
+        other => Err(format!("Invalid theme preference: {}", other)),
+    }
+}
+
+/// The `main` window's actual current theme, as reported by the OS (or the active override).
+pub(crate) fn current_theme(app: &AppHandle) -> Result<String, String> {
+    let window = app
+        .get_webview_window("main")
+        .ok_or_else(|| "main window not found".to_string())?;
+    let theme = window.theme().map_err(|e| e.to_string())?;
+    Ok(theme_to_str(theme).to_string())
+}
+
+/// Emit the new theme to the web frontend so it can re-style in real time.
+pub(crate) fn emit_theme_changed(app: &AppHandle, theme: Theme) {
+    if let Err(e) = app.emit_to("main", "theme://changed", theme_to_str(theme)) {
+        warn!("Failed to emit theme://changed event: {}", e);
+    }
+}
+
+/// Persist the user's theme override and, if it's a fixed theme, apply it to the window
+/// immediately so the native chrome matches the web UI.
+pub(crate) fn set_theme_preference(app: &AppHandle, preference: String) -> Result<(), String> {
+    let theme = parse_preference(&preference)?;
+
+    let mut settings = load_settings(app);
+    settings.theme_preference = preference.clone();
+    save_settings(app, &settings);
+
+    if let Some(window) = app.get_webview_window("main") {
+        if let Err(e) = window.set_theme(theme) {
+            warn!("Failed to apply theme preference to window: {}", e);
+        }
+    }
+
+    info!("Theme preference set to: {}", preference);
+    Ok(())
+}
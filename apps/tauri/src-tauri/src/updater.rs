@@ -0,0 +1,208 @@
+use log::{error, info, warn};
+use semver::Version;
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_updater::{Update, Updater, UpdaterExt};
+use url::Url;
+
+use crate::{load_settings, save_settings};
+
+/// Template for the updater endpoint; `updater_for_channel` appends `AppSettings.update_channel`
+/// to it as a `channel` query parameter.
+const UPDATE_ENDPOINT_TEMPLATE: &str =
+    "https://nubo.email/releases/{{target}}/{{arch}}/{{current_version}}";
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdateAvailablePayload {
+    pub version: String,
+    pub notes: Option<String>,
+    pub date: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdateProgressPayload {
+    pub downloaded: usize,
+    pub total: Option<u64>,
+    pub percent: Option<f64>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct UpdateErrorPayload {
+    pub message: String,
+}
+
+fn emit_to_main<P: Serialize + Clone>(app: &AppHandle, event: &str, payload: P) {
+    if let Err(e) = app.emit_to("main", event, payload) {
+        warn!("Failed to emit {} event: {}", event, e);
+    }
+}
+
+pub(crate) fn emit_update_available(app: &AppHandle, update: &Update) {
+    emit_to_main(
+        app,
+        "updater://available",
+        UpdateAvailablePayload {
+            version: update.version.clone(),
+            notes: update.body.clone(),
+            date: update.date.map(|d| d.to_string()),
+        },
+    );
+}
+
+pub(crate) fn emit_update_progress(app: &AppHandle, downloaded: usize, total: Option<u64>) {
+    let percent = total.map(|t| (downloaded as f64 / t as f64) * 100.0);
+    if let Some(p) = percent {
+        info!("Download progress: {:.1}%", p);
+    }
+    emit_to_main(
+        app,
+        "updater://progress",
+        UpdateProgressPayload {
+            downloaded,
+            total,
+            percent,
+        },
+    );
+}
+
+pub(crate) fn emit_update_downloaded(app: &AppHandle) {
+    info!("Download complete, preparing to install...");
+    emit_to_main(app, "updater://downloaded", ());
+}
+
+pub(crate) fn emit_update_installed(app: &AppHandle) {
+    emit_to_main(app, "updater://installed", ());
+}
+
+pub(crate) fn emit_update_error(app: &AppHandle, message: &str) {
+    emit_to_main(
+        app,
+        "updater://error",
+        UpdateErrorPayload {
+            message: message.to_string(),
+        },
+    );
+}
+
+/// Download and install an already-detected update, emitting progress/lifecycle events.
+pub(crate) async fn install_update(app: &AppHandle, update: Update) {
+    info!("Downloading update...");
+
+    let progress_app = app.clone();
+    let downloaded_app = app.clone();
+
+    match update
+        .download_and_install(
+            move |downloaded, total| emit_update_progress(&progress_app, downloaded, total),
+            move || emit_update_downloaded(&downloaded_app),
+        )
+        .await
+    {
+        Ok(_) => {
+            info!("Update installed successfully. Will apply on next restart.");
+            emit_update_installed(app);
+        }
+        Err(e) => {
+            error!("Failed to install update: {}", e);
+            emit_update_error(app, &e.to_string());
+        }
+    }
+}
+
+/// Build an updater scoped to the given channel by appending a `channel` query parameter to
+/// each endpoint. The value is appended via `query_pairs_mut` rather than `format!`'d in, since
+/// `channel` ultimately comes from the `set_update_channel` command and a raw `&` would let it
+/// inject extra query parameters into the update-check request.
+pub(crate) fn updater_for_channel(app: &AppHandle, channel: &str) -> tauri::Result<Updater> {
+    let mut url =
+        Url::parse(UPDATE_ENDPOINT_TEMPLATE).expect("updater endpoint template is a valid URL");
+    url.query_pairs_mut().append_pair("channel", channel);
+    app.updater_builder().endpoints(vec![url])?.build()
+}
+
+/// A version gate modelled on Tauri's `should_install`, plus channel-aware pre-release handling: reject
+/// downgrades outright, and only let pre-release versions through on non-stable channels.
+pub(crate) fn should_install(current: &str, candidate: &str, channel: &str) -> bool {
+    let current_version = match Version::parse(current) {
+        Ok(v) => v,
+        Err(e) => {
+            warn!("Failed to parse current version '{}': {}", current, e);
+            return true;
+        }
+    };
+    let candidate_version = match Version::parse(candidate) {
+        Ok(v) => v,
+        Err(e) => {
+            warn!("Failed to parse candidate version '{}': {}", candidate, e);
+            return false;
+        }
+    };
+
+    if candidate_version <= current_version {
+        info!(
+            "Rejecting update {} -> {}: not newer than current version",
+            current_version, candidate_version
+        );
+        return false;
+    }
+
+    if channel == "stable" && !candidate_version.pre.is_empty() {
+        info!(
+            "Rejecting pre-release {} on stable channel",
+            candidate_version
+        );
+        return false;
+    }
+
+    true
+}
+
+/// Check for updates and install silently, emitting `updater://*` events along the way.
+pub(crate) async fn check_and_install_update(app: &AppHandle) {
+    info!("Checking for updates...");
+
+    let settings = load_settings(app);
+
+    let updater = match updater_for_channel(app, &settings.update_channel) {
+        Ok(u) => u,
+        Err(e) => {
+            warn!("Failed to get updater: {}", e);
+            emit_update_error(app, &e.to_string());
+            return;
+        }
+    };
+
+    match updater.check().await {
+        Ok(Some(update)) => {
+            info!(
+                "Update available: {} -> {}",
+                env!("CARGO_PKG_VERSION"),
+                update.version
+            );
+            emit_update_available(app, &update);
+
+            if !should_install(
+                env!("CARGO_PKG_VERSION"),
+                &update.version,
+                &settings.update_channel,
+            ) {
+                return;
+            }
+
+            // Save current version for rollback, and retain its installer for real rollback
+            let mut settings = settings;
+            settings.last_version = Some(env!("CARGO_PKG_VERSION").to_string());
+            save_settings(app, &settings);
+            crate::rollback::retain_current_build(app).await;
+
+            install_update(app, update).await;
+        }
+        Ok(None) => {
+            info!("No updates available");
+        }
+        Err(e) => {
+            warn!("Failed to check for updates: {}", e);
+            emit_update_error(app, &e.to_string());
+        }
+    }
+}